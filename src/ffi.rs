@@ -0,0 +1,124 @@
+//! Raw FFI bindings to ANGLE's shader translator (`GLSLANG/ShaderLang.h`).
+//!
+//! These are thin, unsafe declarations. See the `hl` module for a safe
+//! wrapper.
+
+#![allow(non_camel_case_types, non_upper_case_globals, non_snake_case)]
+
+use libc::{c_char, c_int, c_uint, c_void};
+
+pub type ShHandle = *mut c_void;
+
+pub type ShShaderType = c_uint;
+pub const SH_FRAGMENT_SHADER: ShShaderType = 0x8B30;
+pub const SH_VERTEX_SHADER: ShShaderType = 0x8B31;
+pub const SH_COMPUTE_SHADER: ShShaderType = 0x91B9;
+pub const SH_GEOMETRY_SHADER: ShShaderType = 0x8DD9;
+
+pub type ShShaderSpec = c_int;
+pub const SH_GLES2_SPEC: ShShaderSpec = 0x8B40;
+pub const SH_WEBGL_SPEC: ShShaderSpec = 0x8B41;
+pub const SH_GLES3_SPEC: ShShaderSpec = 0x8B42;
+pub const SH_WEBGL2_SPEC: ShShaderSpec = 0x8B43;
+pub const SH_WEBGL3_SPEC: ShShaderSpec = 0x8B44;
+
+pub type ShShaderOutput = c_int;
+pub const SH_GLSL_COMPATIBILITY_OUTPUT: ShShaderOutput = 0x8B45;
+pub const SH_ESSL_OUTPUT: ShShaderOutput = 0x8B46;
+pub const SH_HLSL_3_0_OUTPUT: ShShaderOutput = 0x8B48;
+pub const SH_HLSL_4_1_OUTPUT: ShShaderOutput = 0x8B49;
+pub const SH_SPIRV_VULKAN_OUTPUT: ShShaderOutput = 0x8B4A;
+pub const SH_MSL_METAL_OUTPUT: ShShaderOutput = 0x8B4B;
+
+pub type ShCompileOptions = u64;
+pub const SH_VALIDATE: ShCompileOptions = 0;
+pub const SH_VALIDATE_LOOP_INDEXING: ShCompileOptions = 1 << 0;
+pub const SH_INTERMEDIATE_TREE: ShCompileOptions = 1 << 1;
+pub const SH_OBJECT_CODE: ShCompileOptions = 1 << 2;
+pub const SH_VARIABLES: ShCompileOptions = 1 << 3;
+pub const SH_EMULATE_BUILT_IN_FUNCTIONS: ShCompileOptions = 1 << 4;
+pub const SH_ENFORCE_PACKING_RESTRICTIONS: ShCompileOptions = 1 << 5;
+pub const SH_INIT_OUTPUT_VARIABLES: ShCompileOptions = 1 << 6;
+pub const SH_LIMIT_EXPRESSION_COMPLEXITY: ShCompileOptions = 1 << 7;
+pub const SH_LIMIT_CALL_STACK_DEPTH: ShCompileOptions = 1 << 8;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct ShBuiltInResources {
+    pub MaxVertexAttribs: c_int,
+    pub MaxVertexUniformVectors: c_int,
+    pub MaxVaryingVectors: c_int,
+    pub MaxVertexTextureImageUnits: c_int,
+    pub MaxCombinedTextureImageUnits: c_int,
+    pub MaxTextureImageUnits: c_int,
+    pub MaxFragmentUniformVectors: c_int,
+    pub MaxDrawBuffers: c_int,
+
+    pub OES_standard_derivatives: c_int,
+    pub OES_EGL_image_external: c_int,
+    pub ARB_texture_rectangle: c_int,
+    pub EXT_draw_buffers: c_int,
+    pub FragmentPrecisionHigh: c_int,
+    pub EXT_frag_depth: c_int,
+    pub EXT_shader_texture_lod: c_int,
+
+    pub MaxVertexOutputVectors: c_int,
+    pub MaxFragmentInputVectors: c_int,
+    pub MinProgramTexelOffset: c_int,
+    pub MaxProgramTexelOffset: c_int,
+
+    pub MaxExpressionComplexity: c_int,
+    pub MaxCallStackDepth: c_int,
+}
+
+/// The scalar fields of a reflected `sh::ShaderVariable`, shared by
+/// uniforms, attributes, varyings and output variables.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct ShVariableInfo {
+    pub type_: c_int,
+    pub precision: c_int,
+    pub array_size: c_int,
+}
+
+extern "C" {
+    pub fn ShInitialize() -> c_int;
+    pub fn ShFinalize() -> c_int;
+    pub fn ShInitBuiltInResources(resources: *mut ShBuiltInResources);
+
+    pub fn ShConstructCompiler(shader_type: ShShaderType,
+                                spec: ShShaderSpec,
+                                output: ShShaderOutput,
+                                resources: *const ShBuiltInResources)
+                                -> ShHandle;
+    pub fn ShDestruct(handle: ShHandle);
+
+    pub fn ShCompile(handle: ShHandle,
+                      strings: *const *const c_char,
+                      num_strings: usize,
+                      compile_options: ShCompileOptions)
+                      -> c_int;
+
+    pub fn ShGetObjectCode(handle: ShHandle) -> *const c_char;
+    pub fn ShGetInfoLog(handle: ShHandle) -> *const c_char;
+
+    // The `ShGet*`, `ShVariableList*` and `ShNameHashingMap*` functions below
+    // wrap ANGLE's reflection results (its `std::vector<sh::ShaderVariable>`
+    // lists and its name hashing map) as flat, index-addressable lists so they
+    // can be read from Rust without exposing C++ container internals.
+    pub fn ShGetUniforms(handle: ShHandle) -> *const c_void;
+    pub fn ShGetAttributes(handle: ShHandle) -> *const c_void;
+    pub fn ShGetVaryings(handle: ShHandle) -> *const c_void;
+    pub fn ShGetOutputVariables(handle: ShHandle) -> *const c_void;
+
+    pub fn ShVariableListSize(list: *const c_void) -> usize;
+    pub fn ShVariableListName(list: *const c_void, index: usize) -> *const c_char;
+    pub fn ShVariableListMappedName(list: *const c_void, index: usize) -> *const c_char;
+    pub fn ShVariableListInfo(list: *const c_void, index: usize) -> ShVariableInfo;
+    pub fn ShVariableListFields(list: *const c_void, index: usize) -> *const c_void;
+
+    pub fn ShGetNameHashingMap(handle: ShHandle) -> *const c_void;
+    pub fn ShNameHashingMapSize(map: *const c_void) -> usize;
+    pub fn ShNameHashingMapOriginal(map: *const c_void, index: usize) -> *const c_char;
+    pub fn ShNameHashingMapMapped(map: *const c_void, index: usize) -> *const c_char;
+}
@@ -0,0 +1,638 @@
+//! A high-level, safe wrapper around ANGLE's shader translator.
+
+use ffi::*;
+use libc::{c_char, c_int, c_void};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::mem;
+
+/// Initializes the underlying shader translator. Must be called before
+/// constructing any `ShaderValidator`.
+pub fn initialize() -> Result<(), String> {
+    unsafe {
+        if ShInitialize() == 0 {
+            Err("ShInitialize failed".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Shuts the shader translator down. Should only be called once no more
+/// `ShaderValidator`s are alive.
+pub fn finalize() -> Result<(), String> {
+    unsafe {
+        if ShFinalize() == 0 {
+            Err("ShFinalize failed".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The output language the translator should target.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// Desktop GLSL.
+    Glsl,
+    /// GLSL ES, for mobile/WebGL drivers.
+    Essl,
+    /// HLSL 4.1, targeting Direct3D 11.
+    Hlsl4,
+    /// SPIR-V, targeting Vulkan.
+    Spirv,
+    /// Metal Shading Language, targeting Metal.
+    Msl,
+}
+
+impl Output {
+    fn to_ffi(self) -> ShShaderOutput {
+        match self {
+            Output::Glsl => SH_GLSL_COMPATIBILITY_OUTPUT,
+            Output::Essl => SH_ESSL_OUTPUT,
+            Output::Hlsl4 => SH_HLSL_4_1_OUTPUT,
+            Output::Spirv => SH_SPIRV_VULKAN_OUTPUT,
+            Output::Msl => SH_MSL_METAL_OUTPUT,
+        }
+    }
+}
+
+/// The GLSL ES / WebGL spec a `ShaderValidator` should validate against.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Spec {
+    /// GLES2, i.e. the spec WebGL1 is based on.
+    Gles2,
+    /// WebGL1.
+    Webgl,
+    /// GLES3 / GLSL ES 3.00, i.e. the spec WebGL2 is based on.
+    Gles3,
+    /// WebGL2.
+    Webgl2,
+    /// WebGL3 draft (GLSL ES 3.10-ish).
+    Webgl3,
+}
+
+impl Spec {
+    fn to_ffi(self) -> ShShaderSpec {
+        match self {
+            Spec::Gles2 => SH_GLES2_SPEC,
+            Spec::Webgl => SH_WEBGL_SPEC,
+            Spec::Gles3 => SH_GLES3_SPEC,
+            Spec::Webgl2 => SH_WEBGL2_SPEC,
+            Spec::Webgl3 => SH_WEBGL3_SPEC,
+        }
+    }
+}
+
+/// The resource limits the translator should validate and enforce against
+/// the shaders it compiles.
+pub struct BuiltInResources(ShBuiltInResources);
+
+impl Default for BuiltInResources {
+    fn default() -> Self {
+        unsafe {
+            let mut resources: ShBuiltInResources = mem::zeroed();
+            ShInitBuiltInResources(&mut resources);
+            BuiltInResources(resources)
+        }
+    }
+}
+
+impl BuiltInResources {
+    fn as_ffi(&self) -> &ShBuiltInResources {
+        &self.0
+    }
+
+    pub fn max_vertex_attribs(mut self, value: i32) -> Self {
+        self.0.MaxVertexAttribs = value;
+        self
+    }
+
+    pub fn max_vertex_uniform_vectors(mut self, value: i32) -> Self {
+        self.0.MaxVertexUniformVectors = value;
+        self
+    }
+
+    pub fn max_varying_vectors(mut self, value: i32) -> Self {
+        self.0.MaxVaryingVectors = value;
+        self
+    }
+
+    pub fn max_vertex_texture_image_units(mut self, value: i32) -> Self {
+        self.0.MaxVertexTextureImageUnits = value;
+        self
+    }
+
+    pub fn max_combined_texture_image_units(mut self, value: i32) -> Self {
+        self.0.MaxCombinedTextureImageUnits = value;
+        self
+    }
+
+    pub fn max_texture_image_units(mut self, value: i32) -> Self {
+        self.0.MaxTextureImageUnits = value;
+        self
+    }
+
+    pub fn max_fragment_uniform_vectors(mut self, value: i32) -> Self {
+        self.0.MaxFragmentUniformVectors = value;
+        self
+    }
+
+    pub fn max_draw_buffers(mut self, value: i32) -> Self {
+        self.0.MaxDrawBuffers = value;
+        self
+    }
+
+    /// Enables or disables `GL_OES_standard_derivatives` (`dFdx`/`dFdy`/`fwidth`).
+    pub fn oes_standard_derivatives(mut self, value: bool) -> Self {
+        self.0.OES_standard_derivatives = value as c_int;
+        self
+    }
+
+    /// Enables or disables `GL_OES_EGL_image_external` (sampling external textures).
+    pub fn oes_egl_image_external(mut self, value: bool) -> Self {
+        self.0.OES_EGL_image_external = value as c_int;
+        self
+    }
+
+    /// Enables or disables `GL_ARB_texture_rectangle` (rectangle texture sampling).
+    pub fn arb_texture_rectangle(mut self, value: bool) -> Self {
+        self.0.ARB_texture_rectangle = value as c_int;
+        self
+    }
+
+    /// Enables or disables `GL_EXT_draw_buffers` (multiple fragment outputs).
+    pub fn ext_draw_buffers(mut self, value: bool) -> Self {
+        self.0.EXT_draw_buffers = value as c_int;
+        self
+    }
+
+    /// Whether `highp` precision is available in the fragment shader.
+    pub fn fragment_precision_high(mut self, value: bool) -> Self {
+        self.0.FragmentPrecisionHigh = value as c_int;
+        self
+    }
+
+    /// Enables or disables `GL_EXT_frag_depth` (`gl_FragDepthEXT`).
+    pub fn ext_frag_depth(mut self, value: bool) -> Self {
+        self.0.EXT_frag_depth = value as c_int;
+        self
+    }
+
+    /// Enables or disables `GL_EXT_shader_texture_lod` (explicit LOD texture sampling).
+    pub fn ext_shader_texture_lod(mut self, value: bool) -> Self {
+        self.0.EXT_shader_texture_lod = value as c_int;
+        self
+    }
+
+    pub fn max_vertex_output_vectors(mut self, value: i32) -> Self {
+        self.0.MaxVertexOutputVectors = value;
+        self
+    }
+
+    pub fn max_fragment_input_vectors(mut self, value: i32) -> Self {
+        self.0.MaxFragmentInputVectors = value;
+        self
+    }
+
+    pub fn min_program_texel_offset(mut self, value: i32) -> Self {
+        self.0.MinProgramTexelOffset = value;
+        self
+    }
+
+    pub fn max_program_texel_offset(mut self, value: i32) -> Self {
+        self.0.MaxProgramTexelOffset = value;
+        self
+    }
+
+    /// Caps the depth of nested expressions the translator will accept,
+    /// rejecting shaders that exceed it rather than risking a stack
+    /// overflow while translating them. WebGL implementations use this to
+    /// bound how expensive an untrusted shader can be to compile. The limit
+    /// is only checked when compiling with
+    /// `CompileOptions::limit_expression_complexity` enabled.
+    pub fn max_expression_complexity(mut self, value: i32) -> Self {
+        self.0.MaxExpressionComplexity = value;
+        self
+    }
+
+    /// Caps the depth of the function call stack the translator will
+    /// accept, for the same reason as `max_expression_complexity`. The limit
+    /// is only checked when compiling with
+    /// `CompileOptions::limit_call_stack_depth` enabled.
+    pub fn max_call_stack_depth(mut self, value: i32) -> Self {
+        self.0.MaxCallStackDepth = value;
+        self
+    }
+}
+
+/// The `ShCompileOptions` flags to pass to `ShCompile`, as a builder.
+///
+/// `object_code` is on by default, since without it `compile_and_translate`
+/// has nothing to return; every other flag defaults to off, matching
+/// ANGLE's own defaults. `SH_VARIABLES` is always passed, whatever the
+/// builder says, so that the reflection methods of `ShaderValidator` have
+/// variables to report.
+#[derive(Copy, Clone, Debug)]
+pub struct CompileOptions {
+    object_code: bool,
+    intermediate_tree: bool,
+    emulate_built_in_functions: bool,
+    validate_loop_indexing: bool,
+    enforce_packing_restrictions: bool,
+    init_output_variables: bool,
+    limit_expression_complexity: bool,
+    limit_call_stack_depth: bool,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        CompileOptions {
+            object_code: true,
+            intermediate_tree: false,
+            emulate_built_in_functions: false,
+            validate_loop_indexing: false,
+            enforce_packing_restrictions: false,
+            init_output_variables: false,
+            limit_expression_complexity: false,
+            limit_call_stack_depth: false,
+        }
+    }
+}
+
+impl CompileOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Emit object code (the translated shader source) rather than just
+    /// validating. On by default.
+    pub fn object_code(mut self, value: bool) -> Self {
+        self.object_code = value;
+        self
+    }
+
+    /// Also output the intermediate AST. This does not turn off
+    /// `object_code`; translated source is still emitted unless that is
+    /// disabled too.
+    pub fn intermediate_tree(mut self, value: bool) -> Self {
+        self.intermediate_tree = value;
+        self
+    }
+
+    /// Emulate built-ins like `pow`/`mod` known to be broken on some
+    /// drivers, rather than emitting them directly.
+    pub fn emulate_built_in_functions(mut self, value: bool) -> Self {
+        self.emulate_built_in_functions = value;
+        self
+    }
+
+    /// Validate that loop indexing meets the restrictions of the spec being
+    /// compiled against.
+    pub fn validate_loop_indexing(mut self, value: bool) -> Self {
+        self.validate_loop_indexing = value;
+        self
+    }
+
+    /// Enforce the uniform/varying packing limits of the target resources.
+    pub fn enforce_packing_restrictions(mut self, value: bool) -> Self {
+        self.enforce_packing_restrictions = value;
+        self
+    }
+
+    /// Initialize output variables to zero at the top of `main`, so that
+    /// drivers which don't otherwise guarantee this don't read garbage.
+    pub fn init_output_variables(mut self, value: bool) -> Self {
+        self.init_output_variables = value;
+        self
+    }
+
+    /// Reject shaders whose expressions are deep enough to risk blowing the
+    /// translator's stack.
+    pub fn limit_expression_complexity(mut self, value: bool) -> Self {
+        self.limit_expression_complexity = value;
+        self
+    }
+
+    /// Reject shaders whose function call stack is deep enough to risk
+    /// blowing the translator's stack.
+    pub fn limit_call_stack_depth(mut self, value: bool) -> Self {
+        self.limit_call_stack_depth = value;
+        self
+    }
+
+    fn to_ffi(self) -> ShCompileOptions {
+        let mut options = SH_VALIDATE | SH_VARIABLES;
+        if self.object_code {
+            options |= SH_OBJECT_CODE;
+        }
+        if self.intermediate_tree {
+            options |= SH_INTERMEDIATE_TREE;
+        }
+        if self.emulate_built_in_functions {
+            options |= SH_EMULATE_BUILT_IN_FUNCTIONS;
+        }
+        if self.validate_loop_indexing {
+            options |= SH_VALIDATE_LOOP_INDEXING;
+        }
+        if self.enforce_packing_restrictions {
+            options |= SH_ENFORCE_PACKING_RESTRICTIONS;
+        }
+        if self.init_output_variables {
+            options |= SH_INIT_OUTPUT_VARIABLES;
+        }
+        if self.limit_expression_complexity {
+            options |= SH_LIMIT_EXPRESSION_COMPLEXITY;
+        }
+        if self.limit_call_stack_depth {
+            options |= SH_LIMIT_CALL_STACK_DEPTH;
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod compile_options_tests {
+    use super::*;
+
+    #[test]
+    fn default_omits_emulate_built_in_functions() {
+        assert_eq!(CompileOptions::new().to_ffi() & SH_EMULATE_BUILT_IN_FUNCTIONS, 0);
+    }
+
+    #[test]
+    fn emulate_built_in_functions_sets_the_flag() {
+        let options = CompileOptions::new().emulate_built_in_functions(true).to_ffi();
+        assert_eq!(options & SH_EMULATE_BUILT_IN_FUNCTIONS, SH_EMULATE_BUILT_IN_FUNCTIONS);
+    }
+}
+
+/// The severity of a `Diagnostic`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single entry parsed out of ANGLE's info log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The source line the diagnostic refers to, if ANGLE reported one.
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+/// Parses ANGLE's `ERROR: <source>:<line>: <message>` / `WARNING: ...`
+/// info log format into structured diagnostics, dropping anything it
+/// doesn't recognize.
+fn parse_diagnostics(log: &str) -> Vec<Diagnostic> {
+    let mut result = Vec::new();
+    for line in log.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (severity, rest) = if let Some(rest) = line.strip_prefix("ERROR: ") {
+            (Severity::Error, rest)
+        } else if let Some(rest) = line.strip_prefix("WARNING: ") {
+            (Severity::Warning, rest)
+        } else {
+            continue;
+        };
+
+        let parts: Vec<&str> = rest.splitn(3, ':').collect();
+        let (line_number, message) = match parts.len() {
+            // "<source>:<line>: <message>"
+            3 => match parts[1].trim().parse::<u32>() {
+                Ok(line_number) => (Some(line_number), parts[2].trim().to_string()),
+                // "<source>: <message>", where the message itself contains ':'
+                Err(_) => (None, rest[parts[0].len() + 1..].trim().to_string()),
+            },
+            // "<source>: <message>", with no line number
+            2 => (None, parts[1].trim().to_string()),
+            _ => (None, rest.trim().to_string()),
+        };
+
+        result.push(Diagnostic {
+            severity,
+            line: line_number,
+            message,
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_diagnostics_maps_error_to_its_source_line() {
+        let diagnostics = parse_diagnostics("ERROR: 0:3: 'foo' : undeclared identifier");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert_eq!(diagnostics[0].message, "'foo' : undeclared identifier");
+    }
+}
+
+/// A reflected shader interface variable: a uniform, attribute, varying, or
+/// output variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShaderVariable {
+    /// The name as it appears in the original shader source.
+    pub name: String,
+    /// The name ANGLE mapped it to in the translated output.
+    pub mapped_name: String,
+    /// The GL type enum of the variable (e.g. `GL_FLOAT_VEC4`).
+    pub ty: u32,
+    /// The GLSL ES precision qualifier of the variable.
+    pub precision: i32,
+    /// The number of elements if this is an array, otherwise 1.
+    pub array_size: usize,
+    /// The nested fields of this variable, if it's a struct.
+    pub fields: Vec<ShaderVariable>,
+}
+
+unsafe fn read_variable_list(list: *const c_void) -> Vec<ShaderVariable> {
+    if list.is_null() {
+        return Vec::new();
+    }
+
+    let len = ShVariableListSize(list);
+    let mut result = Vec::with_capacity(len);
+    for index in 0..len {
+        let info = ShVariableListInfo(list, index);
+        let name = CStr::from_ptr(ShVariableListName(list, index)).to_string_lossy().into_owned();
+        let mapped_name = CStr::from_ptr(ShVariableListMappedName(list, index))
+            .to_string_lossy()
+            .into_owned();
+        let fields = read_variable_list(ShVariableListFields(list, index));
+
+        result.push(ShaderVariable {
+            name,
+            mapped_name,
+            ty: info.type_ as u32,
+            precision: info.precision,
+            array_size: info.array_size as usize,
+            fields,
+        });
+    }
+
+    result
+}
+
+/// A shader compiler and translator for a particular shader stage and
+/// output language.
+pub struct ShaderValidator {
+    handle: ShHandle,
+}
+
+impl Drop for ShaderValidator {
+    fn drop(&mut self) {
+        unsafe { ShDestruct(self.handle) }
+    }
+}
+
+impl ShaderValidator {
+    /// Creates a new `ShaderValidator` for the WebGL1 / GLES2 spec.
+    ///
+    /// This is a convenience shorthand for `for_spec(Spec::Webgl, ...)`; use
+    /// `for_spec` directly to validate against WebGL2/GLES3 or later specs,
+    /// or to accept compute (`SH_COMPUTE_SHADER`) or geometry
+    /// (`SH_GEOMETRY_SHADER`) stages.
+    pub fn for_webgl(shader_type: u32,
+                      output: Output,
+                      resources: &BuiltInResources)
+                      -> Option<Self> {
+        Self::for_spec(Spec::Webgl, shader_type, output, resources)
+    }
+
+    /// Creates a new `ShaderValidator` for an explicit `Spec`.
+    pub fn for_spec(spec: Spec,
+                     shader_type: u32,
+                     output: Output,
+                     resources: &BuiltInResources)
+                     -> Option<Self> {
+        let handle = unsafe {
+            ShConstructCompiler(shader_type,
+                                 spec.to_ffi(),
+                                 output.to_ffi(),
+                                 resources.as_ffi())
+        };
+
+        if handle.is_null() {
+            return None;
+        }
+
+        Some(ShaderValidator { handle })
+    }
+
+    /// Compiles and translates `strings` with the default `CompileOptions`,
+    /// returning the translated shader bytes on success, or the shader's
+    /// parsed diagnostics on failure. Use `warnings` to retrieve warnings
+    /// on the success path.
+    pub fn compile_and_translate(&self, strings: &[&str]) -> Result<Vec<u8>, Vec<Diagnostic>> {
+        self.compile_and_translate_with_options(strings, &CompileOptions::default())
+    }
+
+    /// Like `compile_and_translate`, but with explicit `CompileOptions`.
+    pub fn compile_and_translate_with_options(&self,
+                                               strings: &[&str],
+                                               options: &CompileOptions)
+                                               -> Result<Vec<u8>, Vec<Diagnostic>> {
+        let c_strings: Vec<CString> = match strings.iter().map(|s| CString::new(*s)).collect() {
+            Ok(c_strings) => c_strings,
+            Err(_) => {
+                return Err(vec![Diagnostic {
+                    severity: Severity::Error,
+                    line: None,
+                    message: "shader source contains an embedded NUL byte".to_string(),
+                }])
+            }
+        };
+        let ptrs: Vec<*const c_char> = c_strings.iter().map(|s| s.as_ptr()).collect();
+
+        let ok = unsafe {
+            ShCompile(self.handle,
+                      ptrs.as_ptr(),
+                      ptrs.len(),
+                      options.to_ffi())
+        };
+
+        if ok == 0 {
+            return Err(parse_diagnostics(&self.info_log()));
+        }
+
+        unsafe {
+            let object_code = ShGetObjectCode(self.handle);
+            Ok(CStr::from_ptr(object_code).to_bytes().to_vec())
+        }
+    }
+
+    fn info_log(&self) -> String {
+        unsafe {
+            let log = ShGetInfoLog(self.handle);
+            CStr::from_ptr(log).to_string_lossy().into_owned()
+        }
+    }
+
+    /// The warnings from the last call to `compile_and_translate`, whether
+    /// or not it succeeded.
+    pub fn warnings(&self) -> Vec<Diagnostic> {
+        parse_diagnostics(&self.info_log())
+            .into_iter()
+            .filter(|d| d.severity == Severity::Warning)
+            .collect()
+    }
+
+    /// The uniforms declared by the last successfully translated shader.
+    pub fn uniforms(&self) -> Vec<ShaderVariable> {
+        unsafe { read_variable_list(ShGetUniforms(self.handle)) }
+    }
+
+    /// The attributes declared by the last successfully translated shader.
+    pub fn attributes(&self) -> Vec<ShaderVariable> {
+        unsafe { read_variable_list(ShGetAttributes(self.handle)) }
+    }
+
+    /// The varyings declared by the last successfully translated shader.
+    pub fn varyings(&self) -> Vec<ShaderVariable> {
+        unsafe { read_variable_list(ShGetVaryings(self.handle)) }
+    }
+
+    /// The fragment output variables declared by the last successfully
+    /// translated shader.
+    pub fn output_variables(&self) -> Vec<ShaderVariable> {
+        unsafe { read_variable_list(ShGetOutputVariables(self.handle)) }
+    }
+
+    /// The table mapping original shader source names to the mangled names
+    /// ANGLE produced in the translated output, so callers can resolve a
+    /// name the application knows about to the one that actually appears in
+    /// the generated code.
+    pub fn name_map(&self) -> HashMap<String, String> {
+        unsafe {
+            let map = ShGetNameHashingMap(self.handle);
+            if map.is_null() {
+                return HashMap::new();
+            }
+
+            let len = ShNameHashingMapSize(map);
+            let mut result = HashMap::with_capacity(len);
+            for index in 0..len {
+                let original = CStr::from_ptr(ShNameHashingMapOriginal(map, index))
+                    .to_string_lossy()
+                    .into_owned();
+                let mapped = CStr::from_ptr(ShNameHashingMapMapped(map, index))
+                    .to_string_lossy()
+                    .into_owned();
+                result.insert(original, mapped);
+            }
+
+            result
+        }
+    }
+}
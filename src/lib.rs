@@ -5,16 +5,14 @@ pub mod hl;
 
 #[cfg(test)]
 mod tests {
-    use ffi::*;
     use hl::*;
-    use std::sync::{ONCE_INIT, Once};
-    static GLSLANG_INITIALIZATION: Once = ONCE_INIT;
+    use std::sync::Once;
+    static GLSLANG_INITIALIZATION: Once = Once::new();
 
     fn init() {
         GLSLANG_INITIALIZATION.call_once(|| initialize().unwrap());
     }
 
-
     #[test]
     fn test_linkage() {
         init();
@@ -44,10 +42,10 @@ mod tests {
 
     #[test]
     fn test_translation() {
-        const SHADER: &'static str = "void main() {
+        const SHADER: &str = "void main() {
     gl_FragColor = vec4(0, 1, 0, 1);  // green
 }";
-        const EXPECTED: &'static str = "void main(){
+        const EXPECTED: &str = "void main(){
 (gl_FragColor = vec4(0.0, 1.0, 0.0, 1.0));
 }\n";
         const FRAGMENT_SHADER: u32 = 0x8B30;
@@ -61,16 +59,17 @@ mod tests {
 
         let result = compiler.compile_and_translate(&[SHADER]).unwrap();
         println!("{:?}", result);
-        assert!(result == EXPECTED);
+        assert!(result == EXPECTED.as_bytes());
     }
 
     // TODO(emilio): run this test. We can't actually run it because travis machines can't output
     // essl.
+    #[allow(dead_code)]
     fn test_translation_essl() {
-        const SHADER: &'static str = "void main() {
+        const SHADER: &str = "void main() {
     gl_FragColor = vec4(0, 1, 0, 1);  // green
 }";
-        const EXPECTED: &'static str = "void main(){
+        const EXPECTED: &str = "void main(){
 (gl_FragColor = vec4(0.0, 1.0, 0.0, 1.0));
 }\n";
         const FRAGMENT_SHADER: u32 = 0x8B30;
@@ -83,6 +82,6 @@ mod tests {
 
         let result = compiler.compile_and_translate(&[SHADER]).unwrap();
         println!("{:?}", result);
-        assert!(result == EXPECTED);
+        assert!(result == EXPECTED.as_bytes());
     }
 }